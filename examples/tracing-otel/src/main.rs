@@ -1,10 +1,12 @@
 use std::path::PathBuf;
+use std::str::FromStr;
 use std::time::Duration;
 
 use clap::Parser;
 use opentelemetry::trace::TracerProvider as _;
 use opentelemetry::{global, KeyValue};
 use opentelemetry_appender_tracing::layer::OpenTelemetryTracingBridge;
+use opentelemetry_otlp::{LogExporterBuilder, SpanExporterBuilder, WithExportConfig};
 use opentelemetry_sdk::logs::LoggerProvider;
 use opentelemetry_sdk::propagation::TraceContextPropagator;
 use opentelemetry_sdk::resource::{EnvResourceDetector, ResourceDetector};
@@ -19,47 +21,136 @@ use opentelemetry_sdk::{
     Resource,
 };
 use opentelemetry_semantic_conventions::{
-    resource::{DEPLOYMENT_ENVIRONMENT_NAME, SERVICE_NAME, SERVICE_VERSION},
+    resource::{
+        DEPLOYMENT_ENVIRONMENT_NAME, HOST_NAME, PROCESS_EXECUTABLE_NAME, PROCESS_PID,
+        SERVICE_INSTANCE_ID, SERVICE_NAME, SERVICE_VERSION,
+    },
     SCHEMA_URL,
 };
 use opentelemetry_tracing::{MetricsLayer, OpenTelemetryLayer};
 use tracing::*;
 use tracing_core::Level;
-use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
+use tracing_subscriber::filter::Targets;
+use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt, Layer};
 
-// Create a Resource that captures information about the entity for which telemetry is recorded.
-fn resource() -> Resource {
+// The service identity: name and version known at build time, plus a random per-process instance id.
+fn service_resource() -> Resource {
     Resource::from_schema_url(
         [
             KeyValue::new(SERVICE_NAME, env!("CARGO_PKG_NAME")),
             KeyValue::new(SERVICE_VERSION, env!("CARGO_PKG_VERSION")),
             KeyValue::new(DEPLOYMENT_ENVIRONMENT_NAME, "develop"),
+            KeyValue::new(SERVICE_INSTANCE_ID, uuid::Uuid::new_v4().to_string()),
+        ],
+        SCHEMA_URL,
+    )
+}
+
+// The host the process is running on, resolved via the `gethostname` crate.
+fn host_resource() -> Resource {
+    Resource::from_schema_url(
+        [KeyValue::new(
+            HOST_NAME,
+            gethostname::gethostname().to_string_lossy().into_owned(),
+        )],
+        SCHEMA_URL,
+    )
+}
+
+// Attributes describing the current process.
+fn process_resource() -> Resource {
+    let executable = std::env::current_exe()
+        .ok()
+        .and_then(|path| path.file_name().map(|name| name.to_string_lossy().into_owned()))
+        .unwrap_or_default();
+    Resource::from_schema_url(
+        [
+            KeyValue::new(PROCESS_PID, std::process::id() as i64),
+            KeyValue::new(PROCESS_EXECUTABLE_NAME, executable),
         ],
         SCHEMA_URL,
     )
 }
 
+// Create a Resource that captures information about the entity for which telemetry is recorded.
+//
+// The service, host and process detectors are merged with the OTEL_RESOURCE_ATTRIBUTES
+// environment so a single enriched Resource is shared by every signal.
+fn resource() -> Resource {
+    service_resource()
+        .merge(&host_resource())
+        .merge(&process_resource())
+        .merge(&EnvResourceDetector::new().detect(Duration::from_secs(5)))
+}
+
 pub struct OtelGuard {
     pub tracer_provider: TracerProvider,
     pub meter_provider: SdkMeterProvider,
     pub logger_provider: LoggerProvider,
+    // Keeps the non-blocking file writer alive; dropping it flushes any buffered logs to disk.
+    pub file_guard: Option<tracing_appender::non_blocking::WorkerGuard>,
+    // Per-provider grace period for the shutdown on drop; the preceding flushes are not time-bounded.
+    pub shutdown_timeout: Duration,
+}
+
+// Run a provider's (blocking) shutdown on a helper thread and wait for at most `timeout`, so a
+// stuck exporter cannot block process exit indefinitely. Any error is collected for reporting.
+fn shutdown_with_timeout<F>(
+    signal: &str,
+    timeout: Duration,
+    errors: &mut Vec<String>,
+    shutdown: F,
+) where
+    F: FnOnce() -> Result<(), String> + Send + 'static,
+{
+    let (tx, rx) = std::sync::mpsc::channel();
+    std::thread::spawn(move || {
+        let _ = tx.send(shutdown());
+    });
+    match rx.recv_timeout(timeout) {
+        Ok(Ok(())) => {}
+        Ok(Err(err)) => errors.push(format!("shutdown {signal}: {err}")),
+        Err(_) => errors.push(format!("shutdown {signal}: timed out after {timeout:?}")),
+    }
 }
 
 impl Drop for OtelGuard {
     fn drop(&mut self) {
+        let timeout = self.shutdown_timeout;
+        let mut errors: Vec<String> = Vec::new();
+
+        // First hand all buffered telemetry to the exporters.
         for (i, r) in self.logger_provider.force_flush().iter().enumerate() {
             if let Err(err) = r {
-                eprintln!("Failed to flush log message {i}: {err:?}");
+                errors.push(format!("flush log batch {i}: {err:?}"));
             }
         }
         if let Err(err) = self.meter_provider.force_flush() {
-            eprintln!("Failed to flush metric messages: {err:?}");
+            errors.push(format!("flush metrics: {err:?}"));
         }
         for (i, r) in self.tracer_provider.force_flush().iter().enumerate() {
             if let Err(err) = r {
-                eprintln!("Failed to flush trace message {i}: {err:?}");
+                errors.push(format!("flush trace batch {i}: {err:?}"));
             }
         }
+
+        // Then shut the providers down in order: logs, then traces, then metrics.
+        let logger = self.logger_provider.clone();
+        shutdown_with_timeout("logs", timeout, &mut errors, move || {
+            logger.shutdown().map_err(|err| format!("{err:?}"))
+        });
+        let tracer = self.tracer_provider.clone();
+        shutdown_with_timeout("traces", timeout, &mut errors, move || {
+            tracer.shutdown().map_err(|err| format!("{err:?}"))
+        });
+        let meter = self.meter_provider.clone();
+        shutdown_with_timeout("metrics", timeout, &mut errors, move || {
+            meter.shutdown().map_err(|err| format!("{err:?}"))
+        });
+
+        for err in &errors {
+            eprintln!("OpenTelemetry shutdown error: {err}");
+        }
     }
 }
 
@@ -70,101 +161,321 @@ pub struct Args {
     #[clap(long, default_value = ".env")]
     pub otel: PathBuf,
 
-    /// The opentelemetry protocol to use. <grpc|http>
+    /// The default opentelemetry protocol to use for every signal. <grpc|http>
     #[clap(long, default_value = "grpc")]
     pub proto: String,
+
+    /// The opentelemetry protocol to use for traces. Falls back to `--proto`. <grpc|http>
+    #[clap(long)]
+    pub traces_proto: Option<String>,
+
+    /// The opentelemetry protocol to use for metrics. Falls back to `--proto`. <grpc|http>
+    #[clap(long)]
+    pub metrics_proto: Option<String>,
+
+    /// The opentelemetry protocol to use for logs. Falls back to `--proto`. <grpc|http>
+    #[clap(long)]
+    pub logs_proto: Option<String>,
+
+    /// Override the collector endpoint for traces.
+    #[clap(long)]
+    pub traces_endpoint: Option<String>,
+
+    /// Override the collector endpoint for metrics.
+    #[clap(long)]
+    pub metrics_endpoint: Option<String>,
+
+    /// Override the collector endpoint for logs.
+    #[clap(long)]
+    pub logs_endpoint: Option<String>,
+
+    /// Override the `RUST_LOG` filtering directives (e.g. `info,my_crate=debug`).
+    #[clap(long)]
+    pub log_filter: Option<String>,
+
+    /// Write a rolling local log file to this directory. Disabled when unset.
+    #[clap(long)]
+    pub log_dir: Option<PathBuf>,
+
+    /// Rotation for the local log file. <minutely|hourly|daily|never>
+    #[clap(long, default_value = "daily")]
+    pub log_rotation: String,
+
+    /// File name prefix for the local log file.
+    #[clap(long, default_value = env!("CARGO_PKG_NAME"))]
+    pub log_prefix: String,
+
+    /// Disable TLS certificate validation for the OTLP/HTTP exporters and ignore any CA or client
+    /// certificate. The gRPC exporters are then given no TLS configuration at all.
+    #[clap(long)]
+    pub insecure: bool,
+
+    /// Custom CA certificate (PEM). Overrides `OTEL_EXPORTER_OTLP_CERTIFICATE`.
+    #[clap(long)]
+    pub tls_ca: Option<PathBuf>,
+
+    /// Client certificate (PEM) for mutual TLS. Overrides `OTEL_EXPORTER_OTLP_CLIENT_CERTIFICATE`.
+    #[clap(long)]
+    pub tls_client_cert: Option<PathBuf>,
+
+    /// Client private key (PEM) for mutual TLS. Overrides `OTEL_EXPORTER_OTLP_CLIENT_KEY`.
+    #[clap(long)]
+    pub tls_client_key: Option<PathBuf>,
+
+    /// Grace period (in seconds) for shutting down each provider on exit. Flushing is not time-bounded.
+    #[clap(long, default_value_t = 5)]
+    pub shutdown_timeout: u64,
+
+    /// Trace sampler. Overrides `OTEL_TRACES_SAMPLER`.
+    /// <always_on|always_off|traceidratio|parentbased_traceidratio>
+    #[clap(long)]
+    pub sampler: Option<String>,
+
+    /// Sampler argument (e.g. the ratio). Overrides `OTEL_TRACES_SAMPLER_ARG`.
+    #[clap(long)]
+    pub sampler_arg: Option<f64>,
 }
 
-fn init_tracing_grpc() -> OtelGuard {
-    let meter_provider = MeterProviderBuilder::default()
-        .with_resource(resource())
-        .with_reader(
-            PeriodicReader::builder(
-                opentelemetry_otlp::new_exporter()
-                    .tonic()
-                    .build_metrics_exporter(
-                        Box::new(DefaultAggregationSelector::new()),
-                        Box::new(DefaultTemporalitySelector::new()),
-                    )
-                    .unwrap(),
-                runtime::Tokio,
-            )
-            .with_interval(std::time::Duration::from_secs(30))
-            .build(),
-        )
-        .with_reader(
-            PeriodicReader::builder(
-                opentelemetry_stdout::MetricsExporter::default(),
-                runtime::Tokio,
-            )
-            .build(),
-        )
-        .build();
+impl Args {
+    /// The protocol to use for traces, falling back to the global `--proto`.
+    fn traces_proto(&self) -> &str {
+        self.traces_proto.as_deref().unwrap_or(&self.proto)
+    }
 
-    global::set_meter_provider(meter_provider.clone());
+    /// The protocol to use for metrics, falling back to the global `--proto`.
+    fn metrics_proto(&self) -> &str {
+        self.metrics_proto.as_deref().unwrap_or(&self.proto)
+    }
 
-    let tracer_provider = opentelemetry_otlp::new_pipeline()
-        .tracing()
-        .with_exporter(opentelemetry_otlp::new_exporter().tonic())
-        .with_trace_config(
-            trace::Config::default()
-                .with_sampler(Sampler::AlwaysOn)
-                .with_id_generator(RandomIdGenerator::default())
-                .with_max_events_per_span(64)
-                .with_max_attributes_per_span(16)
-                .with_max_events_per_span(16)
-                .with_resource(EnvResourceDetector::new().detect(Duration::from_secs(5))),
-        )
-        .install_batch(opentelemetry_sdk::runtime::Tokio)
-        .unwrap();
+    /// The protocol to use for logs, falling back to the global `--proto`.
+    fn logs_proto(&self) -> &str {
+        self.logs_proto.as_deref().unwrap_or(&self.proto)
+    }
+}
 
-    global::set_tracer_provider(tracer_provider.clone());
+// Select the trace sampler from `--sampler`/`OTEL_TRACES_SAMPLER` (and its argument). For
+// `parentbased_traceidratio` the ratio sampler is wrapped in a parent-based one so decisions
+// propagate with the incoming trace context; `traceidratio` uses the ratio sampler alone.
+fn sampler(args: &Args) -> Sampler {
+    let name = args
+        .sampler
+        .clone()
+        .or_else(|| std::env::var("OTEL_TRACES_SAMPLER").ok())
+        .unwrap_or_else(|| "always_on".to_string());
+    let arg = args
+        .sampler_arg
+        .or_else(|| {
+            std::env::var("OTEL_TRACES_SAMPLER_ARG")
+                .ok()
+                .and_then(|value| value.trim().parse().ok())
+        })
+        .unwrap_or(1.0);
+    match name.trim() {
+        "always_on" => Sampler::AlwaysOn,
+        "always_off" => Sampler::AlwaysOff,
+        "traceidratio" => Sampler::TraceIdRatioBased(arg),
+        "parentbased_traceidratio" => {
+            Sampler::ParentBased(Box::new(Sampler::TraceIdRatioBased(arg)))
+        }
+        other => panic!("OpenTelemetry sampler '{other}' not supported"),
+    }
+}
 
-    let logger_provider = opentelemetry_otlp::new_pipeline()
-        .logging()
-        .with_exporter(opentelemetry_otlp::new_exporter().tonic())
-        .install_batch(opentelemetry_sdk::runtime::Tokio)
-        .unwrap();
+// Parse the `RUST_LOG` directives (or the `--log-filter` override) into per-layer `Targets`,
+// falling back to `info` when nothing is configured.
+fn log_targets(args: &Args) -> Targets {
+    let directives = args
+        .log_filter
+        .clone()
+        .or_else(|| std::env::var("RUST_LOG").ok())
+        .unwrap_or_else(|| "info".to_string());
+    Targets::from_str(directives.trim())
+        .unwrap_or_else(|_| Targets::new().with_default(Level::INFO))
+}
 
-    global::set_text_map_propagator(TraceContextPropagator::new());
+// TLS material for the OTLP exporters, resolved from CLI flags and the standard
+// `OTEL_EXPORTER_OTLP_*` environment variables.
+struct TlsConfig {
+    insecure: bool,
+    ca: Option<Vec<u8>>,
+    client_cert: Option<Vec<u8>>,
+    client_key: Option<Vec<u8>>,
+}
 
-    tracing_subscriber::registry()
-        .with(tracing_subscriber::filter::LevelFilter::from_level(
-            Level::INFO,
-        ))
-        .with(tracing_subscriber::fmt::layer())
-        .with(MetricsLayer::new(meter_provider.clone()))
-        .with(OpenTelemetryLayer::new(
-            tracer_provider.tracer(env!("CARGO_PKG_NAME")),
-        ))
-        .with(OpenTelemetryTracingBridge::new(&logger_provider))
-        .init();
+impl TlsConfig {
+    fn from_args(args: &Args) -> std::io::Result<Self> {
+        // A CLI flag takes precedence over the matching environment variable.
+        let read = |cli: &Option<PathBuf>, env: &str| -> std::io::Result<Option<Vec<u8>>> {
+            match cli
+                .clone()
+                .or_else(|| std::env::var_os(env).map(PathBuf::from))
+            {
+                Some(path) => Ok(Some(std::fs::read(path)?)),
+                None => Ok(None),
+            }
+        };
+        let client_cert = read(&args.tls_client_cert, "OTEL_EXPORTER_OTLP_CLIENT_CERTIFICATE")?;
+        let client_key = read(&args.tls_client_key, "OTEL_EXPORTER_OTLP_CLIENT_KEY")?;
+        // A partial client identity would silently disable mTLS; treat it as a hard error.
+        if client_cert.is_some() != client_key.is_some() {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "mutual TLS requires both a client certificate and a client key",
+            ));
+        }
+        Ok(Self {
+            insecure: args.insecure,
+            ca: read(&args.tls_ca, "OTEL_EXPORTER_OTLP_CERTIFICATE")?,
+            client_cert,
+            client_key,
+        })
+    }
 
-    OtelGuard {
-        tracer_provider,
-        meter_provider,
-        logger_provider,
+    // An HTTP client honoring the configured CA root and optional client identity.
+    fn http_client(&self) -> reqwest::Client {
+        let mut builder = reqwest::Client::builder();
+        if self.insecure {
+            builder = builder.danger_accept_invalid_certs(true);
+        } else {
+            if let Some(ca) = &self.ca {
+                builder = builder.add_root_certificate(
+                    reqwest::Certificate::from_pem(ca).expect("invalid CA certificate"),
+                );
+            }
+            if let (Some(cert), Some(key)) = (&self.client_cert, &self.client_key) {
+                let mut pem = cert.clone();
+                pem.extend_from_slice(key);
+                builder = builder
+                    .identity(reqwest::Identity::from_pem(&pem).expect("invalid client identity"));
+            }
+        }
+        builder.build().unwrap()
+    }
+
+    // The matching tonic TLS config, or `None` when plain/insecure transport is requested.
+    fn tonic_tls(&self) -> Option<tonic::transport::ClientTlsConfig> {
+        if self.insecure || (self.ca.is_none() && self.client_cert.is_none()) {
+            return None;
+        }
+        let mut config = tonic::transport::ClientTlsConfig::new();
+        if let Some(ca) = &self.ca {
+            config = config.ca_certificate(tonic::transport::Certificate::from_pem(ca));
+        }
+        if let (Some(cert), Some(key)) = (&self.client_cert, &self.client_key) {
+            config = config.identity(tonic::transport::Identity::from_pem(cert, key));
+        }
+        Some(config)
     }
 }
 
-fn init_tracing_http() -> OtelGuard {
+// Build a span exporter for the requested protocol and optional endpoint override.
+fn span_exporter(proto: &str, endpoint: Option<&str>, tls: &TlsConfig) -> SpanExporterBuilder {
+    match proto {
+        "grpc" => {
+            let mut exporter = opentelemetry_otlp::new_exporter().tonic();
+            if let Some(endpoint) = endpoint {
+                exporter = exporter.with_endpoint(endpoint);
+            }
+            if let Some(config) = tls.tonic_tls() {
+                exporter = exporter.with_tls_config(config);
+            }
+            exporter.into()
+        }
+        "http" => {
+            let mut exporter = opentelemetry_otlp::new_exporter()
+                .http()
+                .with_http_client(tls.http_client());
+            if let Some(endpoint) = endpoint {
+                exporter = exporter.with_endpoint(endpoint);
+            }
+            exporter.into()
+        }
+        other => panic!("OpenTelemetry protocol '{other}' not supported"),
+    }
+}
+
+// Build a metrics exporter for the requested protocol and optional endpoint override.
+fn metrics_exporter(
+    proto: &str,
+    endpoint: Option<&str>,
+    tls: &TlsConfig,
+) -> opentelemetry_otlp::MetricsExporter {
+    match proto {
+        "grpc" => {
+            let mut exporter = opentelemetry_otlp::new_exporter().tonic();
+            if let Some(endpoint) = endpoint {
+                exporter = exporter.with_endpoint(endpoint);
+            }
+            if let Some(config) = tls.tonic_tls() {
+                exporter = exporter.with_tls_config(config);
+            }
+            exporter
+                .build_metrics_exporter(
+                    Box::new(DefaultAggregationSelector::new()),
+                    Box::new(DefaultTemporalitySelector::new()),
+                )
+                .unwrap()
+        }
+        "http" => {
+            let mut exporter = opentelemetry_otlp::new_exporter()
+                .http()
+                .with_http_client(tls.http_client());
+            if let Some(endpoint) = endpoint {
+                exporter = exporter.with_endpoint(endpoint);
+            }
+            exporter
+                .build_metrics_exporter(
+                    Box::new(DefaultAggregationSelector::new()),
+                    Box::new(DefaultTemporalitySelector::new()),
+                )
+                .unwrap()
+        }
+        other => panic!("OpenTelemetry protocol '{other}' not supported"),
+    }
+}
+
+// Build a log exporter for the requested protocol and optional endpoint override.
+fn log_exporter(proto: &str, endpoint: Option<&str>, tls: &TlsConfig) -> LogExporterBuilder {
+    match proto {
+        "grpc" => {
+            let mut exporter = opentelemetry_otlp::new_exporter().tonic();
+            if let Some(endpoint) = endpoint {
+                exporter = exporter.with_endpoint(endpoint);
+            }
+            if let Some(config) = tls.tonic_tls() {
+                exporter = exporter.with_tls_config(config);
+            }
+            exporter.into()
+        }
+        "http" => {
+            let mut exporter = opentelemetry_otlp::new_exporter()
+                .http()
+                .with_http_client(tls.http_client());
+            if let Some(endpoint) = endpoint {
+                exporter = exporter.with_endpoint(endpoint);
+            }
+            exporter.into()
+        }
+        other => panic!("OpenTelemetry protocol '{other}' not supported"),
+    }
+}
+
+fn init_tracing(args: &Args) -> OtelGuard {
+    let tls = TlsConfig::from_args(args).expect("failed to load TLS material");
+
+    // Build the enriched resource once so every signal reports identical attributes
+    // (in particular a single, stable `service.instance.id` for this process).
+    let resource = resource();
+
     let meter_provider = MeterProviderBuilder::default()
-        .with_resource(resource())
+        .with_resource(resource.clone())
         .with_reader(
             PeriodicReader::builder(
-                opentelemetry_otlp::new_exporter()
-                    .http()
-                    .with_http_client(
-                        reqwest::Client::builder()
-                            .danger_accept_invalid_certs(true)
-                            .build()
-                            .unwrap(),
-                    )
-                    .build_metrics_exporter(
-                        Box::new(DefaultAggregationSelector::new()),
-                        Box::new(DefaultTemporalitySelector::new()),
-                    )
-                    .unwrap(),
+                metrics_exporter(
+                    args.metrics_proto(),
+                    args.metrics_endpoint.as_deref(),
+                    &tls,
+                ),
                 runtime::Tokio,
             )
             .with_interval(std::time::Duration::from_secs(30))
@@ -183,22 +494,18 @@ fn init_tracing_http() -> OtelGuard {
 
     let tracer_provider = opentelemetry_otlp::new_pipeline()
         .tracing()
-        .with_exporter(
-            opentelemetry_otlp::new_exporter().http().with_http_client(
-                reqwest::Client::builder()
-                    .danger_accept_invalid_certs(true)
-                    .build()
-                    .unwrap(),
-            ),
-        )
+        .with_exporter(span_exporter(
+            args.traces_proto(),
+            args.traces_endpoint.as_deref(),
+            &tls,
+        ))
         .with_trace_config(
             trace::Config::default()
-                .with_sampler(Sampler::AlwaysOn)
+                .with_sampler(sampler(args))
                 .with_id_generator(RandomIdGenerator::default())
-                .with_max_events_per_span(64)
                 .with_max_attributes_per_span(16)
                 .with_max_events_per_span(16)
-                .with_resource(EnvResourceDetector::new().detect(Duration::from_secs(5))),
+                .with_resource(resource.clone()),
         )
         .install_batch(opentelemetry_sdk::runtime::Tokio)
         .unwrap();
@@ -207,35 +514,56 @@ fn init_tracing_http() -> OtelGuard {
 
     let logger_provider = opentelemetry_otlp::new_pipeline()
         .logging()
-        .with_exporter(
-            opentelemetry_otlp::new_exporter().http().with_http_client(
-                reqwest::Client::builder()
-                    .danger_accept_invalid_certs(true)
-                    .build()
-                    .unwrap(),
-            ),
-        )
+        .with_resource(resource)
+        .with_exporter(log_exporter(
+            args.logs_proto(),
+            args.logs_endpoint.as_deref(),
+            &tls,
+        ))
         .install_batch(opentelemetry_sdk::runtime::Tokio)
         .unwrap();
 
     global::set_text_map_propagator(TraceContextPropagator::new());
 
+    let filter = log_targets(args);
+
+    // Optionally mirror logs to a rolling local file so records survive collector outages.
+    let (file_layer, file_guard) = match &args.log_dir {
+        Some(dir) => {
+            let appender = match args.log_rotation.as_str() {
+                "minutely" => tracing_appender::rolling::minutely(dir, &args.log_prefix),
+                "hourly" => tracing_appender::rolling::hourly(dir, &args.log_prefix),
+                "daily" => tracing_appender::rolling::daily(dir, &args.log_prefix),
+                "never" => tracing_appender::rolling::never(dir, &args.log_prefix),
+                other => panic!("Log rotation '{other}' not supported"),
+            };
+            let (writer, guard) = tracing_appender::non_blocking(appender);
+            let layer = tracing_subscriber::fmt::layer()
+                .with_ansi(false)
+                .with_writer(writer)
+                .with_filter(filter.clone());
+            (Some(layer), Some(guard))
+        }
+        None => (None, None),
+    };
+
     tracing_subscriber::registry()
-        .with(tracing_subscriber::filter::LevelFilter::from_level(
-            Level::INFO,
-        ))
-        .with(tracing_subscriber::fmt::layer())
+        .with(tracing_subscriber::fmt::layer().with_filter(filter.clone()))
+        .with(file_layer)
         .with(MetricsLayer::new(meter_provider.clone()))
-        .with(OpenTelemetryLayer::new(
-            tracer_provider.tracer(env!("CARGO_PKG_NAME")),
-        ))
-        .with(OpenTelemetryTracingBridge::new(&logger_provider))
+        .with(
+            OpenTelemetryLayer::new(tracer_provider.tracer(env!("CARGO_PKG_NAME")))
+                .with_filter(filter.clone()),
+        )
+        .with(OpenTelemetryTracingBridge::new(&logger_provider).with_filter(filter))
         .init();
 
     OtelGuard {
         tracer_provider,
         meter_provider,
         logger_provider,
+        file_guard,
+        shutdown_timeout: Duration::from_secs(args.shutdown_timeout),
     }
 }
 
@@ -271,13 +599,7 @@ async fn main() -> std::io::Result<()> {
         )
     })?;
 
-    let _guard = if args.proto == "grpc" {
-        init_tracing_grpc()
-    } else if args.proto == "http" {
-        init_tracing_http()
-    } else {
-        panic!("OpenTelemetry protocol '{}' not supported", args.proto);
-    };
+    let _guard = init_tracing(&args);
 
     my_instumented_fun().await;
 